@@ -21,6 +21,7 @@
 use std::{
 	env,
 	cmp::Ordering,
+	collections::BTreeMap,
 	fmt,
 };
 
@@ -33,15 +34,37 @@ macro_rules! fatal {
 
 const BREAD_EXPIRATION: u32 = 30;
 const INITIAL_BREAD: u32 = 10;
+const DEFAULT_DEMAND_RATE: u32 = 1;
+const DEFAULT_PRODUCT: &str = "bread";
 
 type Day = u32;
 type Qty = u32;
 type Price = u32;
+type ProductName = String;
 type ParsedEvents = Vec<SellEvent>;
 type Purchases = Vec<Option<Qty>>;
 type Availability = Vec<bool>;
 type Calendar = Vec<Availability>;
 
+/// One line of a purchasing plan: either a fresh order, carrying enough detail
+/// to render a per-day cost breakdown, or a day the family is stuck eating
+/// stale bread
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum PlanEntry {
+	Order {
+		day:			Day,	// day the order is placed / first consumed
+		provider:		usize,
+		price:			Price,	// unit price paid per loaf
+		qty:			Qty,	// loaves purchased
+		days_covered:	Day,	// calendar days this order is eaten over
+		subtotal:		Price,
+	},
+	Stale {
+		day: Day,
+	},
+}
+type Plan = Vec<PlanEntry>;
+
 /// Quantity-Price pair (something like the concept of Key-Value pair)
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct QPPair {
@@ -50,11 +73,16 @@ struct QPPair {
 }
 impl QPPair {
 }
-/// Event where the User can buy bread, it is an entry in the Calendar
+/// Event where the User can buy bread, it is an entry in the Calendar.
+/// The optional third "(day,price,product)" field tags which product is on
+/// offer (defaulting to DEFAULT_PRODUCT), and the optional fourth field is
+/// that provider's delivery lead time in days (defaulting to 0)
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct SellEvent {
 	day:		Day,
 	price:		Price,
+	product:	ProductName,
+	lead_time:	Day,
 }
 impl SellEvent {
 	pub fn new(in_evt_str: &str) -> Self {
@@ -98,9 +126,22 @@ impl SellEvent {
 				fatal!(format!("Entry for Sell Event is invalid: {}",evt_str));
 			},
 		};
+		let product = match split.next() {
+			Some(product_str) => product_str.trim().to_owned(),
+			None => DEFAULT_PRODUCT.to_owned(),
+		};
+		let lead_time = match split.next() {
+			Some(lead_time_str) => match lead_time_str.trim().parse::<Day>() {
+				Ok(l) => l,
+				Err(err) => {fatal!(format!("Error parsing lead_time ({}): {}",lead_time_str,err));},
+			},
+			None => 0,
+		};
 		SellEvent {
 			day,
 			price,
+			product,
+			lead_time,
 		}
 	}
 }
@@ -130,24 +171,33 @@ impl fmt::Display for EventList {
 		f.write_str(&output)
 	}
 }
+/// A single provider's price and delivery lead time (days between an order
+/// being placed and the bread becoming fresh)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Provider {
+	price:		Price,
+	lead_time:	Day,
+}
 #[derive(Debug, Clone, PartialEq, Eq)]
-struct Providers(Vec<Price>);
+struct Providers(Vec<Provider>);
 impl Providers {
 	pub fn new() -> Self {
 		Providers(Vec::new())
 	}
-	pub fn add_provider(&mut self,price: Price) -> usize {
-		if self.find_provider(price).is_some() {
-			fatal!(format!("Provide with price {} already registered, invalid input",price));
+	pub fn add_provider(&mut self,price: Price,lead_time: Day) -> usize {
+		// keyed on (price,lead_time), not price alone: a slow cheap supplier
+		// and a fast supplier that happen to share a price are distinct offers
+		if self.find_provider(price,lead_time).is_some() {
+			fatal!(format!("Provider with price {} and lead_time {} already registered, invalid input",price,lead_time));
 		}
 		let idx=self.0.len();
-		self.0.push(price);
+		self.0.push(Provider{price,lead_time});
 		idx
 	}
-	pub fn find_provider(&self,price: Price) -> Option<usize> {
+	pub fn find_provider(&self,price: Price,lead_time: Day) -> Option<usize> {
 		let mut idx = 0usize;
 		for p in &self.0 {
-			if *p == price {
+			if p.price == price && p.lead_time == lead_time {
 				return Some(idx);
 			}
 			idx += 1;
@@ -156,7 +206,7 @@ impl Providers {
 	}
 	pub fn sort_by_price(&self) -> Vec<usize> {
 
-		let mut pairs: Vec<(usize,Price)> = self.0.iter().enumerate().map(|(k,vptr)|(k,*vptr)).collect();
+		let mut pairs: Vec<(usize,Price)> = self.0.iter().enumerate().map(|(k,provider)|(k,provider.price)).collect();
 		pairs.sort_by(|(_k1,v1),(_k2,v2)|v1.cmp(v2));
 		let sorted: Vec<usize> = pairs
 			.iter()
@@ -166,21 +216,50 @@ impl Providers {
 		sorted
 	}
 }
+/// The calendar and purchasing parameters for a single product. Everything
+/// `solve` needs to plan one product in isolation lives here
 #[derive(Debug, Clone, PartialEq, Eq)]
-struct Environment {
-	event_list:		Vec<EventList>,
-	providers: 		Providers,
-	avail_matrix:	Calendar,
+struct ProductEnvironment {
+	name:				ProductName,
+	event_list:			Vec<EventList>,
+	providers: 			Providers,
+	avail_matrix:		Calendar,
+	order_fee:			Price,
+	holding_cost:		Price,
+	bread_expiration:	Day,
+	initial_bread:		Qty,
+	demand_rate:		Qty,	// loaves consumed per day
 }
-impl Environment {
-	pub fn new() -> Self {
-		Environment {
-			event_list:		Vec::new(),
-			providers:		Providers::new(),
-			avail_matrix:	Vec::new(),
+impl ProductEnvironment {
+	pub fn new(name: ProductName) -> Self {
+		ProductEnvironment {
+			name,
+			event_list:			Vec::new(),
+			providers:			Providers::new(),
+			avail_matrix:		Vec::new(),
+			order_fee:			0,
+			holding_cost:		0,
+			bread_expiration:	BREAD_EXPIRATION,
+			initial_bread:		INITIAL_BREAD,
+			demand_rate:		DEFAULT_DEMAND_RATE,
 		}
 	}
 }
+/// A calendar shared by several independently-planned products (e.g. bread,
+/// sourdough), each with its own shelf life, starting inventory and demand rate
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Environment {
+	products: Vec<ProductEnvironment>,
+}
+/// Selects which algorithm `solve` uses to turn a ProductEnvironment into a Plan
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SolveMode {
+	/// picks the cheapest available bread for each day in isolation; optimal only
+	/// when orders and holding are free
+	Greedy,
+	/// Wagner-Whitin lot-sizing DP; optimal once order_fee/holding_cost are nonzero
+	Exact,
+}
 fn parse_input(args: Vec<String>) -> (Day,ParsedEvents) {
 	// Return values:
 	//		Length of the calendar in Day(s)
@@ -231,14 +310,182 @@ fn parse_input(args: Vec<String>) -> (Day,ParsedEvents) {
 	}
 	(days,events)
 }
-fn make_environment(mut parsed_events: ParsedEvents) -> Environment {
+/// Per-product overrides set via `product.<name>.<field>=value` config keys;
+/// any field left `None` falls back to the scenario-wide default
+#[derive(Debug, Clone, Default)]
+struct ProductOverride {
+	bread_expiration:	Option<Day>,
+	initial_bread:		Option<Qty>,
+	demand_rate:		Option<Qty>,
+}
+/// Scenario parameters read from a config file, as an alternative to the
+/// rigid three-positional-argument CLI form
+struct ScenarioConfig {
+	total_days:			Day,
+	bread_expiration:	Day,
+	initial_bread:		Qty,
+	demand_rate:		Qty,
+	order_fee:			Price,
+	holding_cost:		Price,
+	events:				ParsedEvents,
+	product_overrides:	BTreeMap<ProductName,ProductOverride>,
+}
+fn parse_config_file(path: &str) -> ScenarioConfig {
+	// key=value lines, blank lines and '#' comments ignored; `events` takes the
+	// same "(day,price) (day,price) ..." list the positional CLI form does;
+	// per-product overrides use "product.<name>.<field>=value", e.g.
+	// "product.sourdough.bread_expiration=20"
+
+	let contents = match std::fs::read_to_string(path) {
+		Ok(c) => c,
+		Err(err) => {fatal!(format!("Couldn't read config file ({}): {}",path,err));},
+	};
+
+	let mut total_days: Option<Day> = None;
+	let mut bread_expiration: Day = BREAD_EXPIRATION;
+	let mut initial_bread: Qty = INITIAL_BREAD;
+	let mut demand_rate: Qty = DEFAULT_DEMAND_RATE;
+	let mut order_fee: Price = 0;
+	let mut holding_cost: Price = 0;
+	let mut events: Option<ParsedEvents> = None;
+	let mut product_overrides: BTreeMap<ProductName,ProductOverride> = BTreeMap::new();
+
+	for (line_no,raw_line) in contents.lines().enumerate() {
+		let line = raw_line.trim();
+		if line.is_empty() || line.starts_with('#') {
+			continue;
+		}
+		let mut split = line.splitn(2,'=');
+		let key = split.next().unwrap().trim();
+		let value = match split.next() {
+			Some(v) => v.trim(),
+			None => {fatal!(format!("Missing '=' on config line {}: {}",line_no+1,raw_line));},
+		};
+		match key {
+			"total_days" => {
+				total_days = Some(match value.parse::<Day>() {
+					Ok(d) => d,
+					Err(err) => {fatal!(format!("Error parsing total_days ({}): {}",value,err));},
+				});
+			},
+			"bread_expiration" => {
+				bread_expiration = match value.parse::<Day>() {
+					Ok(d) => d,
+					Err(err) => {fatal!(format!("Error parsing bread_expiration ({}): {}",value,err));},
+				};
+			},
+			"initial_bread" => {
+				initial_bread = match value.parse::<Qty>() {
+					Ok(q) => q,
+					Err(err) => {fatal!(format!("Error parsing initial_bread ({}): {}",value,err));},
+				};
+			},
+			"demand_rate" => {
+				demand_rate = match value.parse::<Qty>() {
+					Ok(q) => q,
+					Err(err) => {fatal!(format!("Error parsing demand_rate ({}): {}",value,err));},
+				};
+			},
+			"order_fee" => {
+				order_fee = parse_price(value,"order_fee");
+			},
+			"holding_cost" => {
+				holding_cost = parse_price(value,"holding_cost");
+			},
+			"events" => {
+				let parsed: ParsedEvents = value
+					.split(" ")
+					.filter(|s| !s.is_empty())
+					.map(SellEvent::new)
+					.collect();
+				if parsed.is_empty() {
+					fatal!("The calendar is empty");
+				}
+				events = Some(parsed);
+			},
+			other if other.starts_with("product.") => {
+				let rest = &other[8..];
+				let mut parts = rest.rsplitn(2,'.');
+				let field = match parts.next() {
+					Some(f) => f,
+					None => {fatal!(format!("Malformed product override key: {}",other));},
+				};
+				let name = match parts.next() {
+					Some(n) if !n.is_empty() => n.to_owned(),
+					_ => {fatal!(format!("Malformed product override key: {}",other));},
+				};
+				let over = product_overrides.entry(name).or_insert_with(ProductOverride::default);
+				match field {
+					"bread_expiration" => {
+						over.bread_expiration = Some(match value.parse::<Day>() {
+							Ok(d) => d,
+							Err(err) => {fatal!(format!("Error parsing {} ({}): {}",other,value,err));},
+						});
+					},
+					"initial_bread" => {
+						over.initial_bread = Some(match value.parse::<Qty>() {
+							Ok(q) => q,
+							Err(err) => {fatal!(format!("Error parsing {} ({}): {}",other,value,err));},
+						});
+					},
+					"demand_rate" => {
+						over.demand_rate = Some(match value.parse::<Qty>() {
+							Ok(q) => q,
+							Err(err) => {fatal!(format!("Error parsing {} ({}): {}",other,value,err));},
+						});
+					},
+					other_field => {
+						fatal!(format!("Unknown product override field: {}",other_field));
+					},
+				}
+			},
+			other => {
+				fatal!(format!("Unknown config key: {}",other));
+			},
+		}
+	}
+
+	let total_days = match total_days {
+		Some(d) => d,
+		None => {fatal!("Config file is missing required key: total_days");},
+	};
+	let events = match events {
+		Some(e) => e,
+		None => {fatal!("Config file is missing required key: events");},
+	};
+
+	ScenarioConfig {
+		total_days,
+		bread_expiration,
+		initial_bread,
+		demand_rate,
+		order_fee,
+		holding_cost,
+		events,
+		product_overrides,
+	}
+}
+fn make_environment(parsed_events: ParsedEvents) -> Environment {
+	// Groups sell events by product, then builds one ProductEnvironment per
+	// product; BTreeMap keeps the resulting product order deterministic
+	let mut by_product: BTreeMap<ProductName,ParsedEvents> = BTreeMap::new();
+	for e in parsed_events {
+		by_product.entry(e.product.clone()).or_insert_with(Vec::new).push(e);
+	}
+	let products = by_product
+		.into_iter()
+		.map(|(name,events)| build_product_environment(name,events))
+		.collect();
+	Environment { products }
+}
+fn build_product_environment(name: ProductName,mut parsed_events: ParsedEvents) -> ProductEnvironment {
 	// If we use the terminology of Reinforcement Learning , then:
-	//		Environment:  
+	//		Environment:
 	//			The calendar with available purchasin days is our Environment
 	//			Environment = Vec<EventList>, where EventList = Vec<SellEvent> + aux stuff
-	//		Actor: 
+	//		Actor:
 	//			is the user who is making purchasing actions
-	let mut environment = Environment::new();
+	let mut environment = ProductEnvironment::new(name);
 
 	parsed_events.sort_by(|e1,e2| {
 		if e1.day > e2.day {
@@ -261,7 +508,7 @@ fn make_environment(mut parsed_events: ParsedEvents) -> Environment {
 		}
 	});
 	for e in &parsed_events {
-		environment.providers.add_provider(e.price);
+		environment.providers.add_provider(e.price,e.lead_time);
 	}
 	let mut daily_events= Vec::new();
 	let num_entries = parsed_events.len();
@@ -290,9 +537,12 @@ fn generate_empty_availability_vec(num_elts: usize) -> Availability {
 
 	std::iter::repeat(false).take(num_elts).collect()
 }
-fn set_availability_at(a: &mut Availability,start_idx: usize,calendar_days: Day) {
-	
-	let mut limit = start_idx + BREAD_EXPIRATION as usize;
+fn set_availability_at(a: &mut Availability,sell_day: usize,calendar_days: Day,bread_expiration: Day,lead_time: Day) {
+	// bread ordered on sell_day isn't fresh until the provider's lead_time has
+	// passed, and then stays fresh for bread_expiration days after that
+
+	let start_idx = sell_day + lead_time as usize;
+	let mut limit = start_idx + bread_expiration as usize;
 	if limit >= calendar_days as usize {
 		limit = calendar_days as usize;
 	}
@@ -300,12 +550,12 @@ fn set_availability_at(a: &mut Availability,start_idx: usize,calendar_days: Day)
 		a[i]=true;
 	}
 }
-fn calculate_bread_availability(environment: &Environment,calendar_days: Day) -> Calendar {
+fn calculate_bread_availability(environment: &ProductEnvironment,calendar_days: Day) -> Calendar {
 	// expands possible purchases into availability of the bread according to expiration date
 	// Availavilty of the bread is a Matrix of boolean values, where:
 	//		true =  bread is still fresh
-	//		false = bread is stale 
-	
+	//		false = bread is stale
+
 	let mut cal: Calendar = Vec::new();
 	for _ in &environment.providers.0 {
 		cal.push(generate_empty_availability_vec(calendar_days as usize));
@@ -313,8 +563,9 @@ fn calculate_bread_availability(environment: &Environment,calendar_days: Day) ->
 
 	for daily_events in &environment.event_list {
 		for single_event in &daily_events.events {
-			let pidx = environment.providers.find_provider(single_event.price).expect("Provider not found");
-			set_availability_at(&mut cal[pidx],single_event.day as usize,calendar_days);
+			let pidx = environment.providers.find_provider(single_event.price,single_event.lead_time).expect("Provider not found");
+			let lead_time = environment.providers.0[pidx].lead_time;
+			set_availability_at(&mut cal[pidx],single_event.day as usize,calendar_days,environment.bread_expiration,lead_time);
 		}
 	}
 	cal
@@ -329,17 +580,25 @@ fn cheapest_bread_for_day(avail_matrix: &Calendar,prov_list: &Vec<usize>, day_nu
 	}
 	None	// this is returned when family must eat stale bread
 }
-fn solve(total_days: Day,environment: &Environment) -> Purchases {
+fn solve(total_days: Day,environment: &ProductEnvironment,mode: SolveMode) -> Plan {
+	// Dispatches to whichever algorithm the caller selected
+
+	match mode {
+		SolveMode::Greedy => solve_greedy(total_days,environment),
+		SolveMode::Exact => solve_exact(total_days,environment),
+	}
+}
+fn solve_greedy(total_days: Day,environment: &ProductEnvironment) -> Plan {
 	// Return Value:
-	//		return the Vector of optimal purchases, where single purchase = Qty
+	//		the purchasing Plan, one entry per order (or per stale day)
 
 	assert_ne!(environment.event_list.len(),0);
 
-	let mut solution: Purchases = Vec::new();
+	let mut plan: Plan = Vec::new();
 	let mut day = 0;
 	assert_eq!(total_days>day,true);
 
-	let providers = environment.providers.sort_by_price();	
+	let providers = environment.providers.sort_by_price();
 	let mut minimum_providers: Vec<Option<usize>> = Vec::new();
 
 	// build the vector of indices of providers with minimal prices
@@ -358,41 +617,60 @@ fn solve(total_days: Day,environment: &Environment) -> Purchases {
 			None => mp_str.push_str(&format!("None,")),
 		}
 	}
-	// build solution vector
+	let demand_rate = environment.demand_rate.max(1);
+	// emits the order for the run of days [run_start,end_day) served by prov_idx;
+	// demand_rate loaves/day are needed for each of the run's day_count days
+	let emit_order = |plan: &mut Plan,prov_idx: usize,run_start: Day,end_day: Day,day_count: i32| {
+		let qty = day_count as Qty * demand_rate;
+		let price = environment.providers.0[prov_idx].price;
+		plan.push(PlanEntry::Order {
+			day:			run_start,
+			provider:		prov_idx,
+			price,
+			qty,
+			days_covered:	end_day - run_start,
+			subtotal:		price * qty,
+		});
+	};
+	// build plan vector
 	let mut counter = 0usize;
 	assert_eq!(counter>=minimum_providers.len(),false);
 	let mut previous: Option<usize> = minimum_providers[0].clone();
-	let mut qty_accum: i32 = 0-INITIAL_BREAD as i32;// negative qty to discount for existing inventory
+	let initial_days: i32 = (environment.initial_bread / demand_rate) as i32;
+	let mut day_accum: i32 = 0-initial_days;// negative day count to discount for existing inventory
+	let mut run_start: Day = 0;	// day the current run of orders began
 	while counter < minimum_providers.len() {
 		match &previous {
 			&Some(prev_prov) => {
 				match minimum_providers[counter] {
 					Some(min_prov) => {
 						if min_prov == prev_prov {
-							// no change in provider possible, so we accumulate qty for the order
+							// no change in provider possible, so we accumulate days for the order
 						} else {
-							solution.push(Some(qty_accum as Qty));
-							qty_accum = 0;	// now we are ready for a new order
+							emit_order(&mut plan,prev_prov,run_start,counter as Day,day_accum);
+							day_accum = 0;	// now we are ready for a new order
+							run_start = counter as Day;
 						}
 					},
 					None => {
-						solution.push(Some(qty_accum as Qty)); // make purchases for previously available bread
-						solution.push(None);	// stale bread is eaten from here
+						emit_order(&mut plan,prev_prov,run_start,counter as Day,day_accum); // make purchases for previously available bread
+						plan.push(PlanEntry::Stale{day: counter as Day});	// stale bread is eaten from here
 					},
 				}
-				qty_accum += 1;
+				day_accum += 1;
 				previous = minimum_providers[counter].clone();
 			},
 			None => {
-				if qty_accum < 0 {
-					qty_accum += 1;			// consume initial bread inventory
+				if day_accum < 0 {
+					day_accum += 1;			// consume initial bread inventory
 				} else {
 					match minimum_providers[counter] {
 						Some(_) => {
-							qty_accum=1;	// all initial inventory was eaten, so we start buying first loaf
+							day_accum=1;	// all initial inventory was eaten, so we start buying the first day's bread
+							run_start = counter as Day;
 						},
 						None => {
-							solution.push(None);	// stale bread is eaten
+							plan.push(PlanEntry::Stale{day: counter as Day});	// stale bread is eaten
 						},
 					}
 					previous = minimum_providers[counter].clone();
@@ -402,11 +680,264 @@ fn solve(total_days: Day,environment: &Environment) -> Purchases {
 		counter += 1;
 	}
 	match &previous {
-		&Some(_) => solution.push(Some(qty_accum as Qty)),
-		None => (),// we already pushed None in steps before
+		&Some(prev_prov) => emit_order(&mut plan,prev_prov,run_start,total_days,day_accum),
+		None => (),// we already pushed Stale entries in steps before
 	}
 
-	solution
+	plan
+}
+fn solve_exact(total_days: Day,environment: &ProductEnvironment) -> Plan {
+	// Wagner-Whitin lot-sizing DP: an order placed on sell-day i covers a
+	// contiguous run of days i..=j, costing order_fee once plus demand_rate
+	// loaves/day at the unit price on day i plus holding_cost for every loaf
+	// kept for every day it sits before being eaten.
+	// F(p) = min cost to have satisfied demand for the first p demand-days
+	// (F(0)=0); we backtrack the chosen (i,j) segments to build the plan.
+	// Days covered by initial_bread need no order and are simply not demanded.
+
+	assert_ne!(environment.event_list.len(),0);
+	assert_eq!(total_days>0,true);
+
+	let demand_rate = environment.demand_rate.max(1);
+	let initial_days = environment.initial_bread / demand_rate;
+	let demand_start = std::cmp::min(initial_days,total_days);
+	let n = (total_days - demand_start) as usize;
+
+	// (provider idx,price) offering the cheapest bread on each sell-day whose
+	// delivery can still land inside the calendar; gated on effective_start
+	// (sell-day + lead_time), NOT the raw sell-day, so a long-lead-time order
+	// placed before demand_start can still be considered if it arrives in time
+	let mut day_price: Vec<(Day,usize,Price)> = environment
+		.event_list
+		.iter()
+		.map(|el| {
+			let event = el.events.iter().min_by_key(|e| e.price).unwrap();
+			let provider = environment.providers.find_provider(event.price,event.lead_time).expect("Provider not found");
+			(el.day,provider,event.price)
+		})
+		.filter(|(d,provider,_)| *d + environment.providers.0[*provider].lead_time < total_days)
+		.collect();
+	day_price.sort_by(|(d1,_,_),(d2,_,_)| d1.cmp(d2));
+
+	#[derive(Debug, Clone, Copy)]
+	enum Choice {
+		Stale,
+		// order_day is when the order is placed (and paid for); coverage_start
+		// is the first demand-day it actually pays for: order_day + lead_time,
+		// clamped up to demand_start if delivery arrives before demand begins
+		Order { order_day: Day, coverage_start: Day, provider: usize },
+	}
+
+	// the holding-cost term is quadratic in span (span*(span-1)/2 loaf-days,
+	// plus span loaf-days for each day delivery precedes coverage_start),
+	// so with large total_days/bread_expiration it can overflow Price (u32)
+	// long before the final plan cost would; accumulate the DP in u64 and
+	// only narrow back to Price once a concrete PlanEntry is built below
+	let mut cost: Vec<u64> = vec![0;n+1];
+	let mut choice: Vec<Choice> = vec![Choice::Stale;n+1];
+
+	for p in 1..=n {
+		let day_p = demand_start + p as Day - 1;	// last day this state covers
+		let mut best: Option<(u64,Day,Day,usize)> = None;
+		for &(i,provider,unit_price) in &day_price {
+			if i > day_p {
+				break;
+			}
+			let lead_time = environment.providers.0[provider].lead_time;
+			let effective_start = i + lead_time;	// first day the bread is fresh
+			if effective_start > day_p {
+				// still in transit on day_p: can't cover it
+				continue;
+			}
+			if day_p - effective_start >= environment.bread_expiration {
+				continue;
+			}
+			let coverage_start = std::cmp::max(effective_start,demand_start);
+			let pi = (coverage_start - demand_start) as usize;
+			let span = (day_p - coverage_start + 1) as u64;
+			let qty = span * demand_rate as u64;
+			// loaves sit from delivery, which can precede coverage_start
+			let wait = (coverage_start - effective_start) as u64;
+			let holding_sum = environment.holding_cost as u64 * demand_rate as u64 * (span * wait + span * (span - 1) / 2);
+			let order_cost = environment.order_fee as u64 + unit_price as u64 * qty + holding_sum;
+			let total = cost[pi] + order_cost;
+			if best.map_or(true,|(b,_,_,_)| total < b) {
+				best = Some((total,i,coverage_start,provider));
+			}
+		}
+		match best {
+			Some((c,order_day,coverage_start,provider)) => {
+				cost[p] = c;
+				choice[p] = Choice::Order{order_day,coverage_start,provider};
+			},
+			None => {
+				// no order can cover day_p: family eats stale bread that day
+				cost[p] = cost[p-1];
+				choice[p] = Choice::Stale;
+			},
+		}
+	}
+
+	// backtrack the chosen segments, then replay them in chronological order
+	let mut segments: Vec<(Option<usize>,Day,Day,Day)> = Vec::new();	// (provider, order_day, from, to)
+	let mut p = n;
+	while p > 0 {
+		let day_p = demand_start + p as Day - 1;
+		match choice[p] {
+			Choice::Order{order_day,coverage_start,provider} => {
+				segments.push((Some(provider),order_day,coverage_start,day_p));
+				p = (coverage_start - demand_start) as usize;
+			},
+			Choice::Stale => {
+				segments.push((None,day_p,day_p,day_p));
+				p -= 1;
+			},
+		}
+	}
+	segments.reverse();
+
+	segments
+		.iter()
+		.map(|(provider,order_day,from,to)| match provider {
+			Some(pidx) => {
+				let span = *to - *from + 1;
+				let price = environment.providers.0[*pidx].price;
+				let span64 = span as u64;
+				let qty64 = span64 * demand_rate as u64;
+				let wait64 = (*from - (*order_day + environment.providers.0[*pidx].lead_time)) as u64;
+				let holding_sum = environment.holding_cost as u64 * demand_rate as u64 * (span64 * wait64 + span64 * (span64 - 1) / 2);
+				let subtotal64 = environment.order_fee as u64 + price as u64 * qty64 + holding_sum;
+				let qty = Qty::try_from(qty64)
+					.unwrap_or_else(|_| {fatal!(format!("Order quantity overflowed: {} loaves",qty64));});
+				let subtotal = Price::try_from(subtotal64)
+					.unwrap_or_else(|_| {fatal!(format!("Order cost overflowed: {}",subtotal64));});
+				PlanEntry::Order {
+					day:			*order_day,
+					provider:		*pidx,
+					price,
+					qty,
+					days_covered:	span,
+					subtotal,
+				}
+			},
+			None => PlanEntry::Stale{day: *to},
+		})
+		.collect()
+}
+/// Dual of `solve_greedy`: instead of a fixed total cost, we have a fixed
+/// total budget and want to maximize the number of days covered by fresh
+/// bread (equivalently minimize stale-bread days), with ties broken by
+/// lowest spend. With a constant demand_rate and no order fee, covering the
+/// cheapest available days first always maximizes coverage for a given
+/// spend, so a price-ascending greedy fill is optimal.
+///
+/// `budget` is a single pool shared across every product in `products`
+/// rather than handed to each independently: every product's candidate days
+/// are ranked together into one list and the fill stops once the combined
+/// spend would exceed `budget`.
+fn solve_within_budget_shared(total_days: Day,products: &[ProductEnvironment],budget: Price) -> Vec<(Plan,usize)> {
+	assert_eq!(total_days>0,true);
+
+	// one candidate list per product: (day,provider,cost of that day's demand),
+	// in day order
+	let mut candidates: Vec<Vec<(Day,usize,Price)>> = Vec::with_capacity(products.len());
+	let mut demand_starts: Vec<Day> = Vec::with_capacity(products.len());
+	for product in products {
+		assert_ne!(product.event_list.len(),0);
+		let demand_rate = product.demand_rate.max(1);
+		let providers = product.providers.sort_by_price();
+		let initial_days = product.initial_bread / demand_rate;
+		let demand_start = std::cmp::min(initial_days,total_days);
+		demand_starts.push(demand_start);
+
+		let mut product_candidates: Vec<(Day,usize,Price)> = Vec::new();
+		for day in demand_start..total_days {
+			if let Some(pidx) = cheapest_bread_for_day(&product.avail_matrix,&providers,day) {
+				product_candidates.push((day,pidx,product.providers.0[pidx].price * demand_rate));
+			}
+		}
+		candidates.push(product_candidates);
+	}
+
+	// rank every (product,day) candidate together by cost, then fill
+	// cheapest-first until the shared budget is spent
+	let mut ranked: Vec<(usize,usize)> = Vec::new();	// (product_idx,candidate_idx)
+	for (product_idx,product_candidates) in candidates.iter().enumerate() {
+		for candidate_idx in 0..product_candidates.len() {
+			ranked.push((product_idx,candidate_idx));
+		}
+	}
+	ranked.sort_by(|&(p1,c1),&(p2,c2)| candidates[p1][c1].2.cmp(&candidates[p2][c2].2));
+	let mut selected: Vec<Vec<bool>> = candidates.iter().map(|c| vec![false;c.len()]).collect();
+	let mut spend: Price = 0;
+	for (product_idx,candidate_idx) in ranked {
+		let day_cost = candidates[product_idx][candidate_idx].2;
+		if spend + day_cost <= budget {
+			spend += day_cost;
+			selected[product_idx][candidate_idx] = true;
+		}
+	}
+
+	// replay each product's decisions independently, merging consecutive
+	// selected days from one provider into a single order
+	products
+		.iter()
+		.enumerate()
+		.map(|(product_idx,product)| {
+			let demand_rate = product.demand_rate.max(1);
+			let demand_start = demand_starts[product_idx];
+			let product_candidates = &candidates[product_idx];
+			let product_selected = &selected[product_idx];
+
+			let total_demand_days = (total_days - demand_start) as usize;
+			let covered_days = product_selected.iter().filter(|c| **c).count();
+			let uncovered_days = total_demand_days - covered_days;
+
+			let mut plan: Plan = Vec::new();
+			let mut cursor = 0usize;
+			let mut current: Option<(usize,Day,Day)> = None;	// (provider,run_start,days)
+			let emit_order = |plan: &mut Plan,prov_idx: usize,run_start: Day,end_day: Day,days: Day| {
+				let qty = days * demand_rate;
+				let price = product.providers.0[prov_idx].price;
+				plan.push(PlanEntry::Order {
+					day:			run_start,
+					provider:		prov_idx,
+					price,
+					qty,
+					days_covered:	end_day - run_start,
+					subtotal:		price * qty,
+				});
+			};
+			for day in demand_start..total_days {
+				let is_candidate = cursor < product_candidates.len() && product_candidates[cursor].0 == day;
+				if is_candidate && product_selected[cursor] {
+					let pidx = product_candidates[cursor].1;
+					match &mut current {
+						Some((cur_prov,_,days)) if *cur_prov == pidx => {*days += 1;},
+						_ => {
+							if let Some((prov,run_start,days)) = current.take() {
+								emit_order(&mut plan,prov,run_start,day,days);
+							}
+							current = Some((pidx,day,1));
+						},
+					}
+				} else {
+					if let Some((prov,run_start,days)) = current.take() {
+						emit_order(&mut plan,prov,run_start,day,days);
+					}
+					plan.push(PlanEntry::Stale{day});
+				}
+				if is_candidate {
+					cursor += 1;
+				}
+			}
+			if let Some((prov,run_start,days)) = current.take() {
+				emit_order(&mut plan,prov,run_start,total_days,days);
+			}
+
+			(plan,uncovered_days)
+		})
+		.collect()
 }
 #[allow(dead_code)]
 fn dump_availability_matrix(c: &Calendar) {
@@ -422,6 +953,26 @@ fn dump_availability_matrix(c: &Calendar) {
 		println!("");
 	}
 }
+fn plan_to_purchases(plan: &Plan) -> Purchases {
+	// drops the per-order detail down to the plain quantity list the original
+	// tool printed, for the legacy (no --format) output
+	plan
+		.iter()
+		.map(|entry| match entry {
+			&PlanEntry::Order{qty,..} => Some(qty),
+			&PlanEntry::Stale{..} => None,
+		})
+		.collect()
+}
+fn count_stale_days(plan: &Plan) -> usize {
+	plan.iter().filter(|entry| matches!(entry,PlanEntry::Stale{..})).count()
+}
+fn grand_total(plan: &Plan) -> Price {
+	plan.iter().map(|entry| match entry {
+		&PlanEntry::Order{subtotal,..} => subtotal,
+		&PlanEntry::Stale{..} => 0,
+	}).sum()
+}
 fn print_purchasing_plan(purchases: &Purchases) {
 
 	println!(
@@ -433,13 +984,344 @@ fn print_purchasing_plan(purchases: &Purchases) {
 			.join(",")
 	);
 }
+fn print_plan_table(plan: &Plan) {
+
+	println!("{: >5} {: >8} {: >8} {: >5} {: >5} {: >9}","Day","Provider","Price","Qty","Days","Subtotal");
+	for entry in plan {
+		match entry {
+			&PlanEntry::Order{day,provider,price,qty,days_covered,subtotal} => {
+				println!("{: >5} {: >8} {: >8} {: >5} {: >5} {: >9}",day,provider,price,qty,days_covered,subtotal);
+			},
+			&PlanEntry::Stale{day} => {
+				println!("{: >5} {: >8} {: >8} {: >5} {: >5} {: >9}",day,"-","-","-","-","STALE");
+			},
+		}
+	}
+	println!("Grand total: {}\t\tStale bread days: {}",grand_total(plan),count_stale_days(plan));
+}
+fn print_plan_csv(plan: &Plan) {
+
+	println!("kind,day,provider,price,qty,days_covered,subtotal");
+	for entry in plan {
+		match entry {
+			&PlanEntry::Order{day,provider,price,qty,days_covered,subtotal} => {
+				println!("order,{},{},{},{},{},{}",day,provider,price,qty,days_covered,subtotal);
+			},
+			&PlanEntry::Stale{day} => {
+				println!("stale,{},,,,,",day);
+			},
+		}
+	}
+	println!("total,,,,,,{}",grand_total(plan));
+	println!("stale_days,,,,,,{}",count_stale_days(plan));
+}
+/// Like `print_plan_csv`, but for more than one product at once: a `product`
+/// column is added so every row of the combined table stays machine-readable
+/// (one banner-free document, not one valid CSV block per product)
+fn print_multi_plan_csv(products: &[ProductEnvironment],plans: &[Plan]) {
+
+	println!("kind,product,day,provider,price,qty,days_covered,subtotal");
+	for (product,plan) in products.iter().zip(plans.iter()) {
+		for entry in plan {
+			match entry {
+				&PlanEntry::Order{day,provider,price,qty,days_covered,subtotal} => {
+					println!("order,{},{},{},{},{},{},{}",product.name,day,provider,price,qty,days_covered,subtotal);
+				},
+				&PlanEntry::Stale{day} => {
+					println!("stale,{},{},,,,,",product.name,day);
+				},
+			}
+		}
+	}
+	for (product,plan) in products.iter().zip(plans.iter()) {
+		println!("total,{},,,,,,{}",product.name,grand_total(plan));
+		println!("stale_days,{},,,,,,{}",product.name,count_stale_days(plan));
+	}
+}
+fn plan_entries_json(plan: &Plan) -> String {
+	let mut entries = String::from("[");
+	for (idx,entry) in plan.iter().enumerate() {
+		if idx > 0 {
+			entries.push(',');
+		}
+		match entry {
+			&PlanEntry::Order{day,provider,price,qty,days_covered,subtotal} => {
+				entries.push_str(&format!(
+					"{{\"kind\":\"order\",\"day\":{},\"provider\":{},\"price\":{},\"qty\":{},\"days_covered\":{},\"subtotal\":{}}}",
+					day,provider,price,qty,days_covered,subtotal
+				));
+			},
+			&PlanEntry::Stale{day} => {
+				entries.push_str(&format!("{{\"kind\":\"stale\",\"day\":{}}}",day));
+			},
+		}
+	}
+	entries.push(']');
+	entries
+}
+fn print_plan_json(plan: &Plan) {
+
+	println!(
+		"{{\"entries\":{},\"grand_total\":{},\"stale_days\":{}}}",
+		plan_entries_json(plan),grand_total(plan),count_stale_days(plan)
+	);
+}
+/// Like `print_plan_json`, but for more than one product at once: every
+/// product's plan is nested under one top-level object instead of printing
+/// one standalone JSON document per product (which, concatenated, is not
+/// itself valid JSON)
+fn print_multi_plan_json(products: &[ProductEnvironment],plans: &[Plan]) {
+
+	let mut products_json = String::from("[");
+	for (idx,(product,plan)) in products.iter().zip(plans.iter()).enumerate() {
+		if idx > 0 {
+			products_json.push(',');
+		}
+		products_json.push_str(&format!(
+			"{{\"product\":{:?},\"entries\":{},\"grand_total\":{},\"stale_days\":{}}}",
+			product.name,plan_entries_json(plan),grand_total(plan),count_stale_days(plan)
+		));
+	}
+	products_json.push(']');
+	let combined_total: Price = plans.iter().map(grand_total).sum();
+	let combined_stale_days: usize = plans.iter().map(count_stale_days).sum();
+	println!(
+		"{{\"products\":{},\"combined_grand_total\":{},\"combined_stale_days\":{}}}",
+		products_json,combined_total,combined_stale_days
+	);
+}
+/// Output format selected with `--format`; `Legacy` is the original flat,
+/// comma-separated quantity list
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+	Legacy,
+	Table,
+	Csv,
+	Json,
+}
+fn parse_price(s: &str,field: &str) -> Price {
+	match s.parse::<Price>() {
+		Ok(v) => v,
+		Err(err) => {fatal!(format!("Error parsing {} ({}): {}",field,s,err));},
+	}
+}
 fn main() {
 
-	let args: Vec<String> = env::args().collect();
-	let (calendar_length,unordered_events) = parse_input(args);
+	let mut args: Vec<String> = env::args().collect();
+	let mut mode = SolveMode::Greedy;
+	let mut order_fee_override: Option<Price> = None;
+	let mut holding_cost_override: Option<Price> = None;
+	let mut budget: Option<Price> = None;
+	let mut format = OutputFormat::Legacy;
+	let mut config_path: Option<String> = None;
+
+	// everything from the first "--flag" onward is optional, order-independent
+	// tail config; the first three positional args are left untouched for
+	// parse_input
+	let tail: Vec<String> = match args.iter().position(|a| a.starts_with("--")) {
+		Some(pos) => args.split_off(pos),
+		None => Vec::new(),
+	};
+	let mut i = 0usize;
+	while i < tail.len() {
+		match tail[i].as_str() {
+			"--exact" => {
+				mode = SolveMode::Exact;
+				i += 1;
+				if let Some(s) = tail.get(i).filter(|s| !s.starts_with("--")) {
+					order_fee_override = Some(parse_price(s,"order_fee"));
+					i += 1;
+				}
+				if let Some(s) = tail.get(i).filter(|s| !s.starts_with("--")) {
+					holding_cost_override = Some(parse_price(s,"holding_cost"));
+					i += 1;
+				}
+			},
+			"--budget" => {
+				i += 1;
+				let amount_str = match tail.get(i) {
+					Some(s) => s,
+					None => {fatal!("--budget requires an amount argument");},
+				};
+				budget = Some(parse_price(amount_str,"budget"));
+				i += 1;
+			},
+			"--format" => {
+				i += 1;
+				let name = match tail.get(i) {
+					Some(s) => s,
+					None => {fatal!("--format requires a value (table, csv, json)");},
+				};
+				format = match name.as_str() {
+					"table" => OutputFormat::Table,
+					"csv" => OutputFormat::Csv,
+					"json" => OutputFormat::Json,
+					other => {fatal!(format!("Unknown --format value: {}",other));},
+				};
+				i += 1;
+			},
+			"--config" => {
+				i += 1;
+				let path = match tail.get(i) {
+					Some(s) => s,
+					None => {fatal!("--config requires a file path argument");},
+				};
+				config_path = Some(path.clone());
+				i += 1;
+			},
+			other => {fatal!(format!("Unknown option: {}",other));},
+		}
+	}
+
+	// a config file replaces the rigid [num_days] [events] positional form;
+	// either way we end up with the same scenario parameters
+	let (calendar_length,unordered_events,bread_expiration,initial_bread,demand_rate,config_order_fee,config_holding_cost,product_overrides) =
+		match config_path {
+			Some(path) => {
+				let cfg = parse_config_file(&path);
+				(cfg.total_days,cfg.events,cfg.bread_expiration,cfg.initial_bread,cfg.demand_rate,cfg.order_fee,cfg.holding_cost,cfg.product_overrides)
+			},
+			None => {
+				let (days,events) = parse_input(args);
+				(days,events,BREAD_EXPIRATION,INITIAL_BREAD,DEFAULT_DEMAND_RATE,0,0,BTreeMap::new())
+			},
+		};
+
 	let mut environment = make_environment(unordered_events);
-	environment.avail_matrix= calculate_bread_availability(&environment,calendar_length);
-	//dump_availability_matrix(&environment.avail_matrix);
-	let solution = solve(calendar_length,&environment);
-	print_purchasing_plan(&solution);
+	let order_fee = order_fee_override.unwrap_or(config_order_fee);
+	let holding_cost = holding_cost_override.unwrap_or(config_holding_cost);
+	for product in &mut environment.products {
+		let over = product_overrides.get(&product.name);
+		product.order_fee = order_fee;
+		product.holding_cost = holding_cost;
+		product.bread_expiration = over.and_then(|o|o.bread_expiration).unwrap_or(bread_expiration);
+		product.initial_bread = over.and_then(|o|o.initial_bread).unwrap_or(initial_bread);
+		product.demand_rate = over.and_then(|o|o.demand_rate).unwrap_or(demand_rate);
+		product.avail_matrix = calculate_bread_availability(product,calendar_length);
+		//dump_availability_matrix(&product.avail_matrix);
+	}
+
+	// each product is planned independently, except a --budget is a single
+	// pool shared across every product rather than handed to each in full
+	let plans: Vec<Plan> = match budget {
+		Some(budget) => solve_within_budget_shared(calendar_length,&environment.products,budget)
+			.into_iter()
+			.map(|(plan,_)| plan)
+			.collect(),
+		None => environment.products.iter().map(|product| solve(calendar_length,product,mode)).collect(),
+	};
+
+	// with more than one product we print a per-product breakdown plus a
+	// combined grand total; for --format csv/json, printing one "=== name ==="
+	// banner per product would make the combined output neither valid CSV nor
+	// valid JSON, so those formats print one combined machine-readable
+	// document instead
+	let multi_product = environment.products.len() > 1;
+	if multi_product && format == OutputFormat::Csv {
+		print_multi_plan_csv(&environment.products,&plans);
+	} else if multi_product && format == OutputFormat::Json {
+		print_multi_plan_json(&environment.products,&plans);
+	} else {
+		let mut combined_total: Price = 0;
+		let mut combined_stale_days: usize = 0;
+		for (product,plan) in environment.products.iter().zip(plans.iter()) {
+			combined_total += grand_total(plan);
+			combined_stale_days += count_stale_days(plan);
+
+			if multi_product {
+				println!("=== {} ===",product.name);
+			}
+			match format {
+				OutputFormat::Legacy => {
+					print_purchasing_plan(&plan_to_purchases(plan));
+					if budget.is_some() {
+						println!("Stale bread days: {}",count_stale_days(plan));
+					}
+				},
+				OutputFormat::Table => print_plan_table(plan),
+				OutputFormat::Csv => print_plan_csv(plan),
+				OutputFormat::Json => print_plan_json(plan),
+			}
+		}
+		if multi_product {
+			println!("Combined grand total: {}\t\tCombined stale bread days: {}",combined_total,combined_stale_days);
+		}
+	}
+}
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	// Builds a single-product ProductEnvironment from a "(day,price[,product[,lead_time]])"
+	// event string, same format `parse_input`/`parse_config_file` accept.
+	fn test_env(events_str: &str,total_days: Day,initial_bread: Qty,demand_rate: Qty) -> ProductEnvironment {
+		let events: ParsedEvents = events_str
+			.split(" ")
+			.filter(|s| !s.is_empty())
+			.map(SellEvent::new)
+			.collect();
+		let mut environment = make_environment(events).products.pop().unwrap();
+		environment.initial_bread = initial_bread;
+		environment.demand_rate = demand_rate;
+		environment.avail_matrix = calculate_bread_availability(&environment,total_days);
+		environment
+	}
+
+	#[test]
+	fn exact_beats_greedy_with_lead_time() {
+		// reviewer repro: a cheap, slow provider placed before demand_start
+		// whose delivery (day + lead_time) still lands inside the demand
+		// window must be considered by solve_exact, not dropped by the
+		// candidate filter
+		let total_days = 40;
+		let environment = test_env("(1,5,bread,15) (10,100,bread,0)",total_days,10,1);
+		let greedy = solve_greedy(total_days,&environment);
+		let exact = solve_exact(total_days,&environment);
+		assert!(
+			grand_total(&exact) <= grand_total(&greedy),
+			"exact ({}) must be <= greedy ({})",grand_total(&exact),grand_total(&greedy)
+		);
+		assert_eq!(grand_total(&greedy),720);
+		assert_eq!(grand_total(&exact),720);
+	}
+
+	#[test]
+	fn exact_beats_greedy_no_lead_time() {
+		// same invariant without any lead_time involved, across several
+		// providers and prices
+		let total_days = 10;
+		let environment = test_env("(1,50,bread,0) (5,40,bread,0) (8,30,bread,0)",total_days,0,1);
+		let greedy = solve_greedy(total_days,&environment);
+		let exact = solve_exact(total_days,&environment);
+		assert!(grand_total(&exact) <= grand_total(&greedy));
+	}
+
+	#[test]
+	fn solve_within_budget_shared_respects_combined_budget() {
+		// two independent products, each individually coverable in full for
+		// 50, must together spend at most the single shared budget
+		let total_days = 11;
+		let bread = test_env("(1,5,bread,0)",total_days,0,1);
+		let sourdough = test_env("(1,5,sourdough,0)",total_days,0,1);
+		let products = vec![bread,sourdough];
+		let plans = solve_within_budget_shared(total_days,&products,50);
+		assert_eq!(plans.len(),2);
+		let combined: Price = plans.iter().map(|(plan,_)| grand_total(plan)).sum();
+		assert!(combined <= 50,"combined spend {} exceeded the shared budget of 50",combined);
+	}
+
+	#[test]
+	fn solve_within_budget_shared_splits_budget_by_price() {
+		// with a budget that covers only some of the combined demand, the
+		// cheaper product's days should be filled first
+		let total_days = 11;
+		let cheap = test_env("(1,2,bread,0)",total_days,0,1);
+		let pricey = test_env("(1,10,sourdough,0)",total_days,0,1);
+		let products = vec![cheap,pricey];
+		let plans = solve_within_budget_shared(total_days,&products,20);
+		let cheap_covered = count_stale_days(&plans[0].0) < (total_days as usize);
+		assert!(cheap_covered);
+		let combined: Price = plans.iter().map(|(plan,_)| grand_total(plan)).sum();
+		assert!(combined <= 20);
+	}
 }